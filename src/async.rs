@@ -1,22 +1,97 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
-use crate::Error;
+use crate::compression::{self, Compression};
+use crate::migration::{self, Migration};
+use crate::{Error, Format, JsonFormat};
 use serde::ser;
 use serde::de;
 
-#[derive(Debug, Clone)]
-pub struct AsyncSzafka<T> {
+#[derive(Clone)]
+pub struct AsyncSzafka<T, F = JsonFormat> {
     pub path: PathBuf,
-    phantom: std::marker::PhantomData<T>,
+    pub(crate) format: F,
+    pub(crate) version: u32,
+    pub(crate) migrations: BTreeMap<u32, Migration>,
+    pub(crate) compression: Option<Compression>,
+    pub(crate) phantom: std::marker::PhantomData<T>,
 }
 
-impl<T: de::DeserializeOwned + ser::Serialize> AsyncSzafka<T> {
+impl<T, F: std::fmt::Debug> std::fmt::Debug for AsyncSzafka<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncSzafka")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .field("version", &self.version)
+            .field("migrations", &self.migrations.keys().collect::<Vec<_>>())
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+impl<T: de::DeserializeOwned + ser::Serialize> AsyncSzafka<T, JsonFormat> {
     pub fn new(path: impl AsRef<std::path::Path>) -> Self {
         Self {
             path: path.as_ref().into(),
-            phantom: std::marker::PhantomData::default(),
+            format: JsonFormat,
+            version: 1,
+            migrations: BTreeMap::new(),
+            compression: None,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: de::DeserializeOwned + ser::Serialize, F: Format> AsyncSzafka<T, F> {
+    /// Use a different [`Format`] for (de)serializing the stored data
+    /// instead of the default pretty JSON.
+    pub fn with_format<F2: Format>(self, format: F2) -> AsyncSzafka<T, F2> {
+        AsyncSzafka {
+            path: self.path,
+            format,
+            version: self.version,
+            migrations: self.migrations,
+            compression: self.compression,
+            phantom: self.phantom,
         }
     }
 
+    /// Compress the serialized data before writing it to disk, and
+    /// transparently decompress it in [`AsyncSzafka::get`]. Files written
+    /// without compression remain readable.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Set the schema version that `save()` stamps new data with, and that
+    /// `get()` migrates older data up to.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Register a migration upgrading data stored at `from_version` to
+    /// `from_version + 1`, applied by `get()` in sequence until the data
+    /// reaches [`AsyncSzafka::current_version`].
+    ///
+    /// Migrations run against an untyped `serde_json::Value`, since their
+    /// whole purpose is reading a shape that no longer matches `T`. This
+    /// means once a migration is registered, [`Format::deserialize`] must be
+    /// able to produce a `serde_json::Value` from the stored bytes (true for
+    /// [`JsonFormat`], not guaranteed for every custom [`Format`]).
+    pub fn with_migration<M>(mut self, from_version: u32, migration: M) -> Self
+    where
+        M: Fn(serde_json::Value) -> Result<serde_json::Value, Error> + Send + Sync + 'static,
+    {
+        self.migrations.insert(from_version, std::sync::Arc::new(migration));
+        self
+    }
+
+    /// The schema version new data is saved with.
+    pub fn current_version(&self) -> u32 {
+        self.version
+    }
+
     /// Overwrite the existing data
     ///
     /// # Examples
@@ -41,9 +116,49 @@ impl<T: de::DeserializeOwned + ser::Serialize> AsyncSzafka<T> {
     /// # })
     /// ```
     pub async fn save(&self, data: &T) -> Result<(), Error> {
-        use tokio::io::AsyncWriteExt;
+        let versioned = crate::envelope::Versioned::new(self.version, data);
+        let file_contents = self.format.serialize(&versioned)?;
+        let file_contents = compression::compress(self.compression, file_contents)?;
+        self.write_atomic(&file_contents).await
+    }
 
-        let file_contents = serde_json::to_string_pretty(data)?;
+    /// Persist `data` alongside a saved-at timestamp and `ttl`, so that
+    /// [`AsyncSzafka::get_if_fresh`] returns `Ok(None)` once `ttl` has elapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use szafka::AsyncSzafka;
+    /// use serde::{Serialize, Deserialize};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Debug, Clone, Serialize, Deserialize)]
+    /// struct Something {
+    ///     name: String,
+    ///     id: u64,
+    /// }
+    ///
+    /// let szafka = AsyncSzafka::new("/tmp/welcome-to-async-szafka-ttl");
+    /// let something = Something {
+    ///     name: String::from("John"),
+    ///     id: 1000,
+    /// };
+    /// szafka.save_with_ttl(&something, Duration::from_secs(60)).await.expect("save failed");
+    /// # })
+    /// ```
+    pub async fn save_with_ttl(&self, data: &T, ttl: std::time::Duration) -> Result<(), Error> {
+        let versioned = crate::envelope::Versioned::with_ttl(self.version, data, ttl);
+        let file_contents = self.format.serialize(&versioned)?;
+        let file_contents = compression::compress(self.compression, file_contents)?;
+        self.write_atomic(&file_contents).await
+    }
+
+    /// Write `contents` to a temp file and atomically rename it over
+    /// `self.path`, so a crash mid-write never leaves the final file
+    /// truncated or half-written.
+    async fn write_atomic(&self, contents: &[u8]) -> Result<(), Error> {
+        use tokio::io::AsyncWriteExt;
 
         if let Some(path) = self.path.parent() {
             if !path.exists() {
@@ -53,24 +168,54 @@ impl<T: de::DeserializeOwned + ser::Serialize> AsyncSzafka<T> {
             }
         }
 
+        let temp_path = self.temp_path();
+
         let mut file = tokio::fs::OpenOptions::new()
             .write(true)
             .create(true)
-            .open(&self.path)
+            .truncate(true)
+            .open(&temp_path)
             .await
             .map_err(Error::OpenFileError)?;
 
-        file.set_len(0_u64)
+        let written = file
+            .write_all(contents)
             .await
-            .map_err(Error::ChangeFileLengthError)?;
+            .map_err(Error::WriteFileError);
+        let synced = match written {
+            Ok(()) => file.sync_all().await.map_err(Error::SyncError),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = synced {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(err);
+        }
 
-        file.write_all(file_contents.as_bytes())
+        if let Err(err) = tokio::fs::rename(&temp_path, &self.path)
             .await
-            .map_err(Error::WriteFileError)?;
+            .map_err(Error::RenameError)
+        {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(err);
+        }
 
         Ok(())
     }
 
+    /// Build a sibling path to write the new contents to before atomically
+    /// renaming it over `self.path`, so a crash mid-write never leaves the
+    /// final file truncated or half-written.
+    fn temp_path(&self) -> PathBuf {
+        use rand::Rng;
+
+        let suffix: u64 = rand::thread_rng().gen();
+        let file_name = match self.path.file_name() {
+            Some(file_name) => format!("{}.{:x}.tmp", file_name.to_string_lossy(), suffix),
+            None => format!("{:x}.tmp", suffix),
+        };
+        self.path.with_file_name(file_name)
+    }
+
     /// Retrieve stored data
     ///
     /// # Examples
@@ -97,15 +242,84 @@ impl<T: de::DeserializeOwned + ser::Serialize> AsyncSzafka<T> {
     /// # })
     /// ```
     pub async fn get(&self) -> Result<T, Error> {
-        let file = tokio::fs::OpenOptions::new()
-            .read(true)
-            .open(&self.path)
+        let bytes = tokio::fs::read(&self.path)
             .await
             .map_err(Error::OpenFileError)?;
+        let bytes = compression::decompress(bytes)?;
+
+        // Migrations operate on an untyped `serde_json::Value`, since the
+        // whole point is to read data whose shape no longer matches `T`. Only
+        // pay for that detour when a migration is actually registered, so
+        // formats without migrations round-trip straight through `Format`.
+        if self.migrations.is_empty() {
+            let versioned: crate::envelope::Versioned<T> = self.format.deserialize(&bytes)?;
+            if versioned.version != self.version {
+                return Err(Error::MigrationError(format!(
+                    "no migration registered to upgrade version {} to {}",
+                    versioned.version, self.version
+                )));
+            }
+            return Ok(versioned.data);
+        }
+
+        let versioned: crate::envelope::Versioned<serde_json::Value> =
+            self.format.deserialize(&bytes)?;
 
-        let file: T = serde_json::from_reader(file.into_std().await)?;
+        let data = migration::apply(versioned.data, versioned.version, self.version, &self.migrations)?;
 
-        Ok(file)
+        Ok(serde_json::from_value(data)?)
+    }
+
+    /// Retrieve stored data, returning `Ok(None)` if it was saved with a TTL
+    /// that has since elapsed. Data saved without a TTL (via
+    /// [`AsyncSzafka::save`]) never expires.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use szafka::AsyncSzafka;
+    /// use serde::{Serialize, Deserialize};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+    /// struct Something {
+    ///     name: String,
+    ///     id: u64,
+    /// }
+    ///
+    /// let szafka = AsyncSzafka::new("/tmp/welcome-to-async-szafka-ttl");
+    /// let something = Something {
+    ///     name: String::from("John"),
+    ///     id: 1000,
+    /// };
+    /// szafka.save_with_ttl(&something, Duration::from_secs(60)).await.expect("save failed");
+    /// assert_eq!(szafka.get_if_fresh().await.expect("get data failed"), Some(something));
+    /// # })
+    /// ```
+    pub async fn get_if_fresh(&self) -> Result<Option<T>, Error> {
+        let bytes = tokio::fs::read(&self.path)
+            .await
+            .map_err(Error::OpenFileError)?;
+        let bytes = compression::decompress(bytes)?;
+
+        let versioned: crate::envelope::Versioned<T> = self.format.deserialize(&bytes)?;
+
+        if versioned.is_expired() {
+            Ok(None)
+        } else {
+            Ok(Some(versioned.data))
+        }
+    }
+
+    /// The time elapsed since data was last saved (via either
+    /// [`AsyncSzafka::save`] or [`AsyncSzafka::save_with_ttl`]), or `None`
+    /// if nothing has been stored yet.
+    pub async fn age(&self) -> Option<std::time::Duration> {
+        let bytes = tokio::fs::read(&self.path).await.ok()?;
+        let bytes = compression::decompress(bytes).ok()?;
+        let versioned: crate::envelope::Versioned<T> = self.format.deserialize(&bytes).ok()?;
+        Some(versioned.age())
     }
 
     /// Check if there is any saved data
@@ -258,4 +472,136 @@ mod tests {
 
         teardown(szafka).await
     }
+
+    #[tokio::test]
+    async fn failed_save_does_not_leak_temp_file() {
+        let dir = format!("/tmp/async-szafka-test-leak-{}", id());
+        let target = format!("{dir}/file");
+        tokio::fs::create_dir_all(&target).await.unwrap();
+
+        let szafka = AsyncSzafka::<Something>::new(&target);
+        assert!(szafka.save(&Something::random()).await.is_err());
+
+        let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+        let mut leftover = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            if entry.file_name().to_string_lossy().ends_with(".tmp") {
+                leftover.push(entry.file_name());
+            }
+        }
+        assert!(leftover.is_empty(), "leftover temp files: {leftover:?}");
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn migration_upgrades_legacy_field_name() {
+        let szafka = get_szafka::<Something>()
+            .await
+            .with_version(2)
+            .with_migration(1, |mut value| {
+                let legacy_name = value["legacy_name"].take();
+                value["name"] = legacy_name;
+                Ok(value)
+            });
+
+        let legacy = crate::envelope::Versioned::new(
+            1,
+            serde_json::json!({ "legacy_name": "John", "id": 1000 }),
+        );
+        tokio::fs::write(&szafka.path, serde_json::to_vec_pretty(&legacy).unwrap())
+            .await
+            .unwrap();
+
+        let retrieved = szafka.get().await.unwrap();
+        assert_eq!(
+            retrieved,
+            Something {
+                name: "John".to_string(),
+                id: 1000,
+            }
+        );
+
+        teardown(szafka).await
+    }
+
+    #[tokio::test]
+    async fn get_and_get_if_fresh_read_back_either_save_method() {
+        let szafka = get_szafka::<Something>().await;
+        let something = Something::random();
+        szafka.save(&something).await.unwrap();
+        assert_eq!(szafka.get_if_fresh().await.unwrap(), Some(something.clone()));
+
+        let with_ttl = Something::random();
+        szafka
+            .save_with_ttl(&with_ttl, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(szafka.get().await.unwrap(), with_ttl);
+
+        teardown(szafka).await
+    }
+
+    #[tokio::test]
+    async fn get_if_fresh_returns_none_once_expired() {
+        let szafka = get_szafka::<Something>().await;
+        let something = Something::random();
+        szafka
+            .save_with_ttl(&something, std::time::Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(szafka.get_if_fresh().await.unwrap(), None);
+
+        teardown(szafka).await
+    }
+
+    #[tokio::test]
+    async fn compression_is_backwards_compatible_with_uncompressed_files() {
+        let szafka = get_szafka::<Something>().await;
+        let something = Something::random();
+        szafka.save(&something).await.unwrap();
+
+        let compressed = szafka.with_compression(Compression::Zstd);
+        assert_eq!(compressed.get().await.unwrap(), something);
+
+        teardown(compressed).await
+    }
+
+    #[tokio::test]
+    async fn migration_error_on_missing_gap() {
+        let szafka = get_szafka::<Something>().await.with_version(3);
+
+        let legacy = crate::envelope::Versioned::new(
+            1,
+            serde_json::json!({ "name": "John", "id": 1000 }),
+        );
+        tokio::fs::write(&szafka.path, serde_json::to_vec_pretty(&legacy).unwrap())
+            .await
+            .unwrap();
+
+        assert!(matches!(szafka.get().await.unwrap_err(), Error::MigrationError(_)));
+
+        teardown(szafka).await
+    }
+
+    #[tokio::test]
+    async fn migration_error_on_future_version() {
+        let szafka = get_szafka::<Something>()
+            .await
+            .with_version(1)
+            .with_migration(1, Ok);
+
+        let future = crate::envelope::Versioned::new(
+            5,
+            serde_json::json!({ "name": "John", "id": 1000 }),
+        );
+        tokio::fs::write(&szafka.path, serde_json::to_vec_pretty(&future).unwrap())
+            .await
+            .unwrap();
+
+        assert!(matches!(szafka.get().await.unwrap_err(), Error::MigrationError(_)));
+
+        teardown(szafka).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use crate::{AsyncSzafka, Error, Format};
+use serde::{de, ser};
+
+/// Async counterpart of [`Store`](crate::Store), implemented by
+/// [`AsyncSzafka`], so generic code can be written once against
+/// `impl AsyncStore<T>` instead of depending on the concrete type.
+#[allow(async_fn_in_trait)]
+pub trait AsyncStore<T> {
+    async fn save(&self, data: &T) -> Result<(), Error>;
+    async fn get(&self) -> Result<T, Error>;
+    fn exists(&self) -> bool;
+    async fn remove(&self) -> Result<(), Error>;
+}
+
+impl<T, F> AsyncStore<T> for AsyncSzafka<T, F>
+where
+    T: de::DeserializeOwned + ser::Serialize,
+    F: Format,
+{
+    async fn save(&self, data: &T) -> Result<(), Error> {
+        AsyncSzafka::save(self, data).await
+    }
+
+    async fn get(&self) -> Result<T, Error> {
+        AsyncSzafka::get(self).await
+    }
+
+    fn exists(&self) -> bool {
+        AsyncSzafka::exists(self)
+    }
+
+    async fn remove(&self) -> Result<(), Error> {
+        AsyncSzafka::remove(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct Something {
+        name: String,
+    }
+
+    /// Exercises a store purely through `impl AsyncStore<T>`, so it only
+    /// compiles if generic code really can be written once against the
+    /// trait instead of the concrete `AsyncSzafka`.
+    async fn round_trip(store: impl AsyncStore<Something>, data: &Something) {
+        store.save(data).await.unwrap();
+        assert!(store.exists());
+        assert_eq!(&store.get().await.unwrap(), data);
+        store.remove().await.unwrap();
+        assert!(!store.exists());
+    }
+
+    #[tokio::test]
+    async fn async_szafka_round_trips_through_the_store_trait() {
+        let szafka = AsyncSzafka::new("/tmp/async-szafka-store-trait-test");
+        round_trip(szafka, &Something { name: "John".to_string() }).await;
+    }
+}
@@ -0,0 +1,92 @@
+use crate::Error;
+
+/// Prefixes a compressed file so `get()` can tell it apart from an
+/// uncompressed one written by an older version of szafka.
+const MAGIC: &[u8] = b"SZFKC1";
+
+/// A compression algorithm applied between serialization and the file I/O
+/// done by `Szafka::save`/`AsyncSzafka::save` (and reversed in `get()`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+}
+
+impl Compression {
+    fn id(self) -> u8 {
+        match self {
+            Compression::Zstd => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn compress(compression: Option<Compression>, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let Some(compression) = compression else {
+        return Ok(bytes);
+    };
+
+    let compressed = match compression {
+        Compression::Zstd => zstd::encode_all(bytes.as_slice(), 0).map_err(Error::CompressionError)?,
+    };
+
+    let mut framed = Vec::with_capacity(MAGIC.len() + 1 + compressed.len());
+    framed.extend_from_slice(MAGIC);
+    framed.push(compression.id());
+    framed.extend_from_slice(&compressed);
+
+    Ok(framed)
+}
+
+pub(crate) fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok(bytes);
+    }
+
+    let id = *bytes.get(MAGIC.len()).ok_or_else(|| {
+        Error::DecompressionError(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "missing compression algorithm byte",
+        ))
+    })?;
+
+    let compression = Compression::from_id(id).ok_or_else(|| {
+        Error::DecompressionError(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unknown compression algorithm id `{id}`"),
+        ))
+    })?;
+
+    let payload = &bytes[MAGIC.len() + 1..];
+
+    match compression {
+        Compression::Zstd => zstd::decode_all(payload).map_err(Error::DecompressionError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zstd_round_trips() {
+        let original = b"hello szafka, compress me".repeat(100);
+        let compressed = compress(Some(Compression::Zstd), original.clone()).unwrap();
+
+        assert!(compressed.starts_with(MAGIC));
+        assert!(compressed.len() < original.len());
+
+        assert_eq!(decompress(compressed).unwrap(), original);
+    }
+
+    #[test]
+    fn decompress_passes_through_uncompressed_bytes_unchanged() {
+        let uncompressed = b"{\"name\":\"John\"}".to_vec();
+        assert_eq!(decompress(uncompressed.clone()).unwrap(), uncompressed);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{AsyncSzafka, Szafka};
+
+/// Switch from the sync wrapper to the async one, keeping the same path,
+/// [`Format`](crate::Format), schema version and migrations.
+impl<T, F> From<Szafka<T, F>> for AsyncSzafka<T, F> {
+    fn from(szafka: Szafka<T, F>) -> Self {
+        Self {
+            path: szafka.path,
+            format: szafka.format,
+            version: szafka.version,
+            migrations: szafka.migrations,
+            compression: szafka.compression,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Switch from the async wrapper to the sync one, keeping the same path,
+/// [`Format`](crate::Format), schema version and migrations.
+impl<T, F> From<AsyncSzafka<T, F>> for Szafka<T, F> {
+    fn from(szafka: AsyncSzafka<T, F>) -> Self {
+        Self {
+            path: szafka.path,
+            format: szafka.format,
+            version: szafka.version,
+            migrations: szafka.migrations,
+            compression: szafka.compression,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Wraps a stored value with the schema version it was written with and the
+/// time it was saved, so `get()` can detect and migrate payloads written by
+/// an older `T` layout, and `get_if_fresh()`/`age()` can reason about a TTL.
+/// `save()` and `save_with_ttl()` both write this same shape, so `get()` and
+/// `get_if_fresh()` can read back what either of them wrote.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Versioned<T> {
+    pub(crate) version: u32,
+    #[serde(default = "now_millis")]
+    saved_at_millis: u64,
+    #[serde(default)]
+    ttl_millis: Option<u64>,
+    pub(crate) data: T,
+}
+
+impl<T> Versioned<T> {
+    pub(crate) fn new(version: u32, data: T) -> Self {
+        Self {
+            version,
+            saved_at_millis: now_millis(),
+            ttl_millis: None,
+            data,
+        }
+    }
+
+    pub(crate) fn with_ttl(version: u32, data: T, ttl: Duration) -> Self {
+        Self {
+            version,
+            saved_at_millis: now_millis(),
+            ttl_millis: Some(ttl.as_millis() as u64),
+            data,
+        }
+    }
+
+    pub(crate) fn age(&self) -> Duration {
+        Duration::from_millis(now_millis().saturating_sub(self.saved_at_millis))
+    }
+
+    /// `false` for data saved without a TTL (via [`Versioned::new`]).
+    pub(crate) fn is_expired(&self) -> bool {
+        match self.ttl_millis {
+            Some(ttl_millis) => self.age().as_millis() as u64 >= ttl_millis,
+            None => false,
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,84 @@
+use crate::Error;
+use serde::{de, ser};
+
+/// A pluggable (de)serialization backend for `Szafka`/`AsyncSzafka`.
+///
+/// Implement this to store data as something other than pretty JSON, e.g. a
+/// compact binary codec like bincode or CBOR.
+pub trait Format: Clone {
+    fn serialize<T: ser::Serialize>(&self, data: &T) -> Result<Vec<u8>, Error>;
+    fn deserialize<T: de::DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error>;
+}
+
+/// The default [`Format`], kept for backwards compatibility: pretty-printed
+/// JSON via `serde_json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormat;
+
+impl Format for JsonFormat {
+    fn serialize<T: ser::Serialize>(&self, data: &T) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec_pretty(data)?)
+    }
+
+    fn deserialize<T: de::DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Szafka;
+    use serde::{Deserialize, Serialize};
+
+    /// A tiny non-JSON [`Format`] that XORs plain JSON bytes against a fixed
+    /// key, used to prove `with_format` actually swaps the wire format
+    /// instead of secretly still going through JSON underneath. It doesn't
+    /// pull in a new crate, unlike a real binary codec such as bincode or
+    /// CBOR would.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct ObfuscatedFormat;
+
+    impl ObfuscatedFormat {
+        const KEY: u8 = 0x5a;
+
+        fn xor(bytes: Vec<u8>) -> Vec<u8> {
+            bytes.into_iter().map(|byte| byte ^ Self::KEY).collect()
+        }
+    }
+
+    impl Format for ObfuscatedFormat {
+        fn serialize<T: ser::Serialize>(&self, data: &T) -> Result<Vec<u8>, Error> {
+            Ok(Self::xor(serde_json::to_vec(data)?))
+        }
+
+        fn deserialize<T: de::DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+            Ok(serde_json::from_slice(&Self::xor(bytes.to_vec()))?)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct Something {
+        name: String,
+        id: u64,
+    }
+
+    #[test]
+    fn round_trips_through_a_non_json_format() {
+        let szafka = Szafka::<Something>::new("/tmp/szafka-format-test-obfuscated")
+            .with_format(ObfuscatedFormat);
+        let something = Something {
+            name: "John".to_string(),
+            id: 1000,
+        };
+
+        szafka.save(&something).unwrap();
+
+        let on_disk = std::fs::read(&szafka.path).unwrap();
+        assert!(!on_disk.starts_with(b"{"), "expected non-JSON bytes on disk");
+
+        assert_eq!(szafka.get().unwrap(), something);
+
+        szafka.remove().unwrap();
+    }
+}
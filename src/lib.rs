@@ -8,6 +8,28 @@ mod r#async;
 #[cfg(any(feature = "async", test))]
 pub use r#async::AsyncSzafka;
 
+mod format;
+pub use format::{Format, JsonFormat};
+
+mod envelope;
+mod migration;
+
+mod compression;
+pub use compression::Compression;
+
+#[cfg(feature = "sync")]
+mod store;
+#[cfg(feature = "sync")]
+pub use store::Store;
+
+#[cfg(any(feature = "async", test))]
+mod async_store;
+#[cfg(any(feature = "async", test))]
+pub use async_store::AsyncStore;
+
+#[cfg(all(feature = "sync", any(feature = "async", test)))]
+mod convert;
+
 
 
 #[derive(Debug, thiserror::Error)]
@@ -22,6 +44,16 @@ pub enum Error {
     WriteFileError(std::io::Error),
     #[error("remove file error: `{0}`")]
     RemoveFileError(std::io::Error),
-    #[error("change file length error: `{0}`")]
-    ChangeFileLengthError(std::io::Error),
+    #[error("rename file error: `{0}`")]
+    RenameError(std::io::Error),
+    #[error("sync file error: `{0}`")]
+    SyncError(std::io::Error),
+    #[error("format error: `{0}`")]
+    SerializeError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("migration error: `{0}`")]
+    MigrationError(String),
+    #[error("compression error: `{0}`")]
+    CompressionError(std::io::Error),
+    #[error("decompression error: `{0}`")]
+    DecompressionError(std::io::Error),
 }
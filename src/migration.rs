@@ -0,0 +1,38 @@
+use crate::Error;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A migration upgrading a stored payload from the version it names to the
+/// next one, expressed in terms of the untyped JSON `Value` the envelope is
+/// kept in until the final version is reached.
+pub(crate) type Migration =
+    Arc<dyn Fn(serde_json::Value) -> Result<serde_json::Value, Error> + Send + Sync>;
+
+/// Apply `migrations` in sequence to bring `data` from `version` up to
+/// `target`.
+pub(crate) fn apply(
+    mut data: serde_json::Value,
+    mut version: u32,
+    target: u32,
+    migrations: &BTreeMap<u32, Migration>,
+) -> Result<serde_json::Value, Error> {
+    while version < target {
+        let migration = migrations.get(&version).ok_or_else(|| {
+            Error::MigrationError(format!(
+                "no migration registered to upgrade version {} to {}",
+                version,
+                version + 1
+            ))
+        })?;
+        data = migration(data)?;
+        version += 1;
+    }
+
+    if version != target {
+        return Err(Error::MigrationError(format!(
+            "stored data is at version {version}, newer than the configured version {target}"
+        )));
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,61 @@
+use crate::{Error, Format, Szafka};
+use serde::{de, ser};
+
+/// Storage operations shared by [`Szafka`], so generic code can be written
+/// once against `impl Store<T>` instead of depending on the concrete type.
+pub trait Store<T> {
+    fn save(&self, data: &T) -> Result<(), Error>;
+    fn get(&self) -> Result<T, Error>;
+    fn exists(&self) -> bool;
+    fn remove(&self) -> Result<(), Error>;
+}
+
+impl<T, F> Store<T> for Szafka<T, F>
+where
+    T: de::DeserializeOwned + ser::Serialize,
+    F: Format,
+{
+    fn save(&self, data: &T) -> Result<(), Error> {
+        Szafka::save(self, data)
+    }
+
+    fn get(&self) -> Result<T, Error> {
+        Szafka::get(self)
+    }
+
+    fn exists(&self) -> bool {
+        Szafka::exists(self)
+    }
+
+    fn remove(&self) -> Result<(), Error> {
+        Szafka::remove(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct Something {
+        name: String,
+    }
+
+    /// Exercises a store purely through `impl Store<T>`, so it only compiles
+    /// if generic code really can be written once against the trait instead
+    /// of the concrete `Szafka`.
+    fn round_trip(store: impl Store<Something>, data: &Something) {
+        store.save(data).unwrap();
+        assert!(store.exists());
+        assert_eq!(&store.get().unwrap(), data);
+        store.remove().unwrap();
+        assert!(!store.exists());
+    }
+
+    #[test]
+    fn szafka_round_trips_through_the_store_trait() {
+        let szafka = Szafka::new("/tmp/szafka-store-trait-test");
+        round_trip(szafka, &Something { name: "John".to_string() });
+    }
+}